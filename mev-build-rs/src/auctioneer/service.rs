@@ -27,10 +27,15 @@ use serde::Deserialize;
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
+    time::{Duration, Instant},
 };
-use tokio::sync::{
-    broadcast,
-    mpsc::{Receiver, Sender},
+use tokio::{
+    sync::{
+        broadcast,
+        mpsc::{Receiver, Sender},
+    },
+    task::JoinSet,
+    time::timeout,
 };
 use tokio_stream::StreamExt;
 use tracing::{debug, error, info, trace, warn};
@@ -39,6 +44,136 @@ use tracing::{debug, error, info, trace, warn};
 // E.g. a value of `2` corresponds to being half-way into the epoch.
 const PROPOSAL_SCHEDULE_INTERVAL: u64 = 2;
 
+// Amount of time to wait for a single relay to respond before treating it as unreachable,
+// so that one slow or hanging relay cannot stall a batch of requests to the others.
+const RELAY_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Default consecutive failures (timeouts or request errors) a relay must accrue before its
+// circuit is tripped open; overridden by `Config::circuit_breaker_failure_threshold`.
+const DEFAULT_CIRCUIT_BREAKER_FAILURE_THRESHOLD: u64 = 5;
+
+// Default seconds a relay's circuit stays open before a single trial request is let through to
+// probe for recovery (half-open); overridden by `Config::circuit_breaker_cooldown_secs`.
+const DEFAULT_CIRCUIT_BREAKER_COOLDOWN_SECS: u64 = 30;
+
+fn default_circuit_breaker_failure_threshold() -> u64 {
+    DEFAULT_CIRCUIT_BREAKER_FAILURE_THRESHOLD
+}
+
+fn default_circuit_breaker_cooldown_secs() -> u64 {
+    DEFAULT_CIRCUIT_BREAKER_COOLDOWN_SECS
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CircuitState {
+    // requests are dispatched to the relay as usual
+    Closed,
+    // requests are skipped until `opened_at.elapsed() >= cooldown`
+    Open,
+    // the cooldown has elapsed; a single trial request is in flight to probe for recovery
+    HalfOpen,
+}
+
+impl Default for CircuitState {
+    fn default() -> Self {
+        Self::Closed
+    }
+}
+
+/// A single relay's reliability as tracked by the circuit breaker: a cumulative success count
+/// and a *consecutive*-failure count (reset to `0` on every success), not a time-windowed
+/// history of recent outcomes.
+#[derive(Debug, Clone, Default)]
+pub struct RelayHealth {
+    pub successes: u64,
+    pub consecutive_failures: u64,
+    pub last_error: Option<String>,
+    pub last_latency: Option<Duration>,
+    state: CircuitState,
+    opened_at: Option<Instant>,
+}
+
+impl RelayHealth {
+    pub fn is_available(&self) -> bool {
+        self.state != CircuitState::Open
+    }
+}
+
+/// Tracks per-relay submit/schedule outcomes and trips a circuit breaker for relays that are
+/// failing repeatedly, so submission deadlines are not wasted waiting on dead endpoints.
+#[derive(Debug)]
+struct RelayHealthTracker {
+    relays: Vec<RelayHealth>,
+    failure_threshold: u64,
+    cooldown: Duration,
+}
+
+impl RelayHealthTracker {
+    fn new(relay_count: usize, failure_threshold: u64, cooldown: Duration) -> Self {
+        Self { relays: vec![RelayHealth::default(); relay_count], failure_threshold, cooldown }
+    }
+
+    // Returns `true` if a request should be dispatched to this relay: the circuit is closed, or
+    // its cooldown has just elapsed and this call is the trial request (half-open).
+    fn should_dispatch(&mut self, relay_index: usize) -> bool {
+        let cooldown = self.cooldown;
+        let health = &mut self.relays[relay_index];
+        match health.state {
+            CircuitState::Closed => true,
+            // the trial request is already in flight; hold further requests until its outcome
+            // is recorded
+            CircuitState::HalfOpen => false,
+            CircuitState::Open => {
+                let cooled_down = health.opened_at.map_or(true, |opened_at| opened_at.elapsed() >= cooldown);
+                if cooled_down {
+                    health.state = CircuitState::HalfOpen;
+                }
+                cooled_down
+            }
+        }
+    }
+
+    fn record_success(&mut self, relay_index: usize, latency: Duration) {
+        let health = &mut self.relays[relay_index];
+        if health.state != CircuitState::Closed {
+            info!(relay_index, "relay recovered, closing circuit");
+        }
+        health.successes += 1;
+        health.consecutive_failures = 0;
+        health.last_latency = Some(latency);
+        health.state = CircuitState::Closed;
+        health.opened_at = None;
+    }
+
+    fn record_failure(&mut self, relay_index: usize, error: String) {
+        let failure_threshold = self.failure_threshold;
+        let health = &mut self.relays[relay_index];
+        health.consecutive_failures += 1;
+        health.last_error = Some(error);
+        match health.state {
+            CircuitState::HalfOpen => {
+                warn!(relay_index, "trial request failed, re-opening circuit");
+                health.state = CircuitState::Open;
+                health.opened_at = Some(Instant::now());
+            }
+            CircuitState::Closed if health.consecutive_failures >= failure_threshold => {
+                warn!(
+                    relay_index,
+                    consecutive_failures = health.consecutive_failures,
+                    "tripping circuit breaker for relay"
+                );
+                health.state = CircuitState::Open;
+                health.opened_at = Some(Instant::now());
+            }
+            _ => {}
+        }
+    }
+
+    fn snapshot(&self) -> &[RelayHealth] {
+        &self.relays
+    }
+}
+
 fn make_attributes_for_proposer(
     attributes: &BuilderPayloadBuilderAttributes,
     proposer: &Proposer,
@@ -52,6 +187,24 @@ fn make_attributes_for_proposer(
     attributes
 }
 
+// Decides whether a newly observed `(hash, fees)` should replace `current` as the best payload
+// tracked for an auction: it should, unless `current` is for the same block (a repeat
+// observation, a no-op) or already has at least as high fees.
+fn should_retain_as_best<Hash, Fees>(
+    current: Option<(Hash, Fees)>,
+    candidate_hash: &Hash,
+    candidate_fees: &Fees,
+) -> bool
+where
+    Hash: PartialEq,
+    Fees: PartialOrd,
+{
+    match current {
+        Some((hash, fees)) if hash == *candidate_hash || fees >= *candidate_fees => false,
+        _ => true,
+    }
+}
+
 fn prepare_submission(
     payload: EthBuiltPayload,
     signing_key: &SecretKey,
@@ -106,15 +259,49 @@ pub struct AuctionContext {
     pub relays: RelaySet,
 }
 
-#[derive(Deserialize, Debug, Default, Clone)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Config {
     /// Secret key used to sign builder messages to relay
     pub secret_key: SecretKey,
     #[serde(skip)]
     /// Public key corresponding to secret key
     pub public_key: BlsPublicKey,
+    /// Additional named builder identities, beyond `secret_key`, that can be presented to
+    /// specific relays via `relay_keys`
+    #[serde(default)]
+    pub builder_keys: HashMap<String, SecretKey>,
+    /// Maps a relay to the name of the builder identity in `builder_keys` that should be
+    /// presented to it. The key must match the *rendered* form of the relay, i.e. what
+    /// `relay.to_string()` produces once the corresponding entry in `relays` has been parsed
+    /// (for example `https://pubkey@relay.example.com`), not necessarily the raw string as
+    /// written in `relays` if parsing normalizes it. Relays absent from this map sign with
+    /// `secret_key`
+    #[serde(default)]
+    pub relay_keys: HashMap<String, String>,
     /// List of relays to submit bids
     pub relays: Vec<String>,
+    /// Consecutive submit/schedule failures a relay must accrue before its circuit breaker trips
+    /// open and it is skipped for `circuit_breaker_cooldown_secs`
+    #[serde(default = "default_circuit_breaker_failure_threshold")]
+    pub circuit_breaker_failure_threshold: u64,
+    /// Seconds a relay's circuit breaker stays open before a single trial request is let through
+    /// to probe for recovery (half-open)
+    #[serde(default = "default_circuit_breaker_cooldown_secs")]
+    pub circuit_breaker_cooldown_secs: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            secret_key: Default::default(),
+            public_key: Default::default(),
+            builder_keys: Default::default(),
+            relay_keys: Default::default(),
+            relays: Default::default(),
+            circuit_breaker_failure_threshold: default_circuit_breaker_failure_threshold(),
+            circuit_breaker_cooldown_secs: default_circuit_breaker_cooldown_secs(),
+        }
+    }
 }
 
 pub struct Service<
@@ -126,7 +313,7 @@ pub struct Service<
     clock: broadcast::Receiver<ClockMessage>,
     builder: PayloadBuilderHandle<Engine>,
     payload_store: PayloadStore<Engine>,
-    relays: Vec<Relay>,
+    relays: Vec<Arc<Relay>>,
     config: Config,
     context: Arc<Context>,
     // TODO consolidate this somewhere...
@@ -137,6 +324,16 @@ pub struct Service<
     auction_schedule: AuctionSchedule,
     open_auctions: HashMap<PayloadId, Arc<AuctionContext>>,
     processed_payload_attributes: HashMap<Slot, HashSet<PayloadId>>,
+    relay_health: RelayHealthTracker,
+    // highest-fee `EthBuiltPayload` observed so far for each open auction, so a `Dispatch` can
+    // target something stable rather than whatever the builder happens to hold at that instant
+    best_payloads: HashMap<PayloadId, EthBuiltPayload>,
+    // named builder identities from `config.builder_keys`, with public keys recomputed from
+    // their secret keys
+    builder_keys: HashMap<String, (SecretKey, BlsPublicKey)>,
+    // the builder key name to present to each relay, indexed the same as `relays`; `None` means
+    // fall back to `config.secret_key`
+    relay_key_names: Vec<Option<String>>,
 }
 
 impl<
@@ -155,12 +352,52 @@ impl<
         context: Arc<Context>,
         genesis_time: u64,
     ) -> Self {
-        let relays =
-            parse_relay_endpoints(&config.relays).into_iter().map(Relay::from).collect::<Vec<_>>();
+        let relays = parse_relay_endpoints(&config.relays)
+            .into_iter()
+            .map(|endpoint| Arc::new(Relay::from(endpoint)))
+            .collect::<Vec<_>>();
 
         config.public_key = config.secret_key.public_key();
 
+        // look up by each relay's own rendered endpoint, rather than by position in
+        // `config.relays`, since `parse_relay_endpoints` is not guaranteed to preserve a strict
+        // 1:1, order-preserving mapping from the configured strings
+        let rendered_relays = relays.iter().map(|relay| relay.to_string()).collect::<HashSet<_>>();
+        let unmatched_relay_keys = config
+            .relay_keys
+            .keys()
+            .filter(|endpoint| !rendered_relays.contains(*endpoint))
+            .cloned()
+            .collect::<Vec<_>>();
+        assert!(
+            unmatched_relay_keys.is_empty(),
+            "relay_keys references relay(s) that do not match any configured relay: {unmatched_relay_keys:?}"
+        );
+
+        let relay_key_names = relays
+            .iter()
+            .map(|relay| config.relay_keys.get(&relay.to_string()).cloned())
+            .collect::<Vec<_>>();
+
+        let builder_keys = config
+            .builder_keys
+            .iter()
+            .map(|(name, secret_key)| (name.clone(), (secret_key.clone(), secret_key.public_key())))
+            .collect::<HashMap<_, _>>();
+
+        for name in config.relay_keys.values() {
+            assert!(
+                builder_keys.contains_key(name),
+                "relay key mapping references unknown builder key `{name}`"
+            );
+        }
+
         let payload_store = builder.clone().into();
+        let relay_health = RelayHealthTracker::new(
+            relays.len(),
+            config.circuit_breaker_failure_threshold,
+            Duration::from_secs(config.circuit_breaker_cooldown_secs),
+        );
 
         Self {
             clock,
@@ -175,24 +412,63 @@ impl<
             auction_schedule: Default::default(),
             open_auctions: Default::default(),
             processed_payload_attributes: Default::default(),
+            relay_health,
+            best_payloads: Default::default(),
+            builder_keys,
+            relay_key_names,
         }
     }
 
+    /// Returns a snapshot of each configured relay's current health, in the same order as
+    /// `relays`, so operators can see which relays are degraded.
+    pub fn relay_health(&self) -> &[RelayHealth] {
+        self.relay_health.snapshot()
+    }
+
     async fn fetch_proposer_schedules(&mut self) {
-        // TODO: consider moving to new task on another thread, can do parallel fetch (join set)
-        // and not block others at this interval
         // TODO: batch updates to auction schedule
         // TODO: consider fast data access once this stabilizes
         // TODO: rework `auction_schedule` so there is no issue with confusing relays and their
         // indices
-        for (relay_index, relay) in self.relays.iter().enumerate() {
-            match relay.get_proposal_schedule().await {
-                Ok(schedule) => {
+
+        // dispatch the fetch to every relay concurrently so a single slow or hanging relay
+        // cannot stall the others for this interval; relays with a tripped circuit are skipped
+        let mut fetches = JoinSet::new();
+        for (relay_index, relay) in self.relays.iter().cloned().enumerate() {
+            if !self.relay_health.should_dispatch(relay_index) {
+                debug!(relay_index, %relay, "skipping proposer schedule fetch, circuit open");
+                continue
+            }
+            fetches.spawn(async move {
+                let start = Instant::now();
+                let result = timeout(RELAY_REQUEST_TIMEOUT, relay.get_proposal_schedule()).await;
+                (relay_index, relay, result, start.elapsed())
+            });
+        }
+
+        // merge results back into `auction_schedule` serially as they arrive, to avoid lock
+        // contention on the shared schedule
+        while let Some(result) = fetches.join_next().await {
+            let (relay_index, relay, result, latency) = match result {
+                Ok(outcome) => outcome,
+                Err(err) => {
+                    error!(%err, "proposer schedule fetch task panicked");
+                    continue
+                }
+            };
+            match result {
+                Ok(Ok(schedule)) => {
+                    self.relay_health.record_success(relay_index, latency);
                     let slots = self.auction_schedule.process(relay_index, &schedule);
                     info!(?slots, %relay, "processed proposer schedule");
                 }
-                Err(err) => {
-                    warn!(err = %err, "error fetching proposer schedule from relay")
+                Ok(Err(err)) => {
+                    self.relay_health.record_failure(relay_index, err.to_string());
+                    warn!(err = %err, %relay, "error fetching proposer schedule from relay")
+                }
+                Err(_) => {
+                    self.relay_health.record_failure(relay_index, "request timed out".to_string());
+                    warn!(%relay, timeout = ?RELAY_REQUEST_TIMEOUT, "timed out fetching proposer schedule from relay")
                 }
             }
         }
@@ -203,6 +479,7 @@ impl<
         if (slot * PROPOSAL_SCHEDULE_INTERVAL) % self.context.slots_per_epoch == 0 {
             self.fetch_proposer_schedules().await;
         }
+        self.poll_best_payloads().await;
     }
 
     async fn on_epoch(&mut self, epoch: Epoch) {
@@ -212,6 +489,33 @@ impl<
         self.auction_schedule.clear(retain_slot);
         self.open_auctions.retain(|_, auction| auction.slot >= retain_slot);
         self.processed_payload_attributes.retain(|&slot, _| slot >= retain_slot);
+        let open_auctions = &self.open_auctions;
+        self.best_payloads.retain(|payload_id, _| open_auctions.contains_key(payload_id));
+    }
+
+    // Retains `payload` as the best observed for `payload_id` if it has higher fees than
+    // whatever (if anything) is currently tracked for that auction, deduping repeat observations
+    // of the same block by hash rather than re-evaluating and re-storing each poll.
+    fn record_best_payload(&mut self, payload_id: PayloadId, payload: EthBuiltPayload) {
+        let current = self.best_payloads.get(&payload_id).map(|best| (best.block().hash(), best.fees()));
+        if should_retain_as_best(current, &payload.block().hash(), &payload.fees()) {
+            self.best_payloads.insert(payload_id, payload);
+        }
+    }
+
+    // Periodically polls the payload builder for the current best payload of every open auction
+    // and retains the highest-fee one seen so far, since a fresh build in progress can otherwise
+    // regress the payload a `Dispatch` would submit.
+    async fn poll_best_payloads(&mut self) {
+        let payload_ids = self.open_auctions.keys().cloned().collect::<Vec<_>>();
+        for payload_id in payload_ids {
+            if let Some(result) = self.payload_store.best_payload(payload_id).await {
+                match result {
+                    Ok(payload) => self.record_best_payload(payload_id, payload),
+                    Err(err) => warn!(%err, %payload_id, "could not poll best payload"),
+                }
+            }
+        }
     }
 
     fn get_proposals(&self, slot: Slot) -> Option<&Proposals> {
@@ -297,8 +601,6 @@ impl<
     async fn process_bid_update(&mut self, message: BidderMessage) {
         match message {
             BidderMessage::RevenueQuery(payload_id, tx) => {
-                // TODO: store this payload (by hash) so that the bid that returns targets something
-                // stable...
                 if let Some(payload) = self.payload_store.best_payload(payload_id).await {
                     match payload {
                         Ok(payload) => {
@@ -322,7 +624,15 @@ impl<
                 // TOOD: backpressure on bidder...?
                 if let Some(payload) = self.payload_store.resolve(payload_id).await {
                     match payload {
-                        Ok(payload) => self.submit_payload(payload).await,
+                        Ok(payload) => {
+                            // submit the best payload observed over the whole auction, not just
+                            // the one resolved at this instant, which can regress if the builder
+                            // just started a fresh build
+                            self.record_best_payload(payload_id, payload);
+                            if let Some(payload) = self.best_payloads.remove(&payload_id) {
+                                self.submit_payload(payload).await;
+                            }
+                        }
                         Err(err) => warn!(%err, "payload resolution failed"),
                     }
                 }
@@ -331,7 +641,7 @@ impl<
         }
     }
 
-    async fn submit_payload(&self, payload: EthBuiltPayload) {
+    async fn submit_payload(&mut self, payload: EthBuiltPayload) {
         let auction = self.open_auctions.get(&payload.id()).expect("has auction");
         let relay_set = auction
             .relays
@@ -349,32 +659,73 @@ impl<
             relays=?relay_set,
             "submitting payload"
         );
-        match prepare_submission(
-            payload,
-            &self.config.secret_key,
-            &self.config.public_key,
-            auction,
-            &self.context,
-        ) {
-            Ok(signed_submission) => {
-                // TODO: parallel dispatch
-                for &relay_index in &auction.relays {
-                    match self.relays.get(relay_index) {
-                        Some(relay) => {
-                            if let Err(err) = relay.submit_bid(&signed_submission).await {
-                                warn!(%err, ?relay, slot = auction.slot, "could not submit payload");
-                            }
+        // group relays by the builder key name that should be presented to them, so we sign once
+        // per distinct key rather than once per relay
+        let mut relay_indices_by_key: HashMap<Option<String>, Vec<usize>> = HashMap::new();
+        for &relay_index in &auction.relays {
+            let key_name = self.relay_key_names.get(relay_index).cloned().flatten();
+            relay_indices_by_key.entry(key_name).or_default().push(relay_index);
+        }
+
+        let mut submissions = JoinSet::new();
+        for (key_name, relay_indices) in relay_indices_by_key {
+            let (secret_key, public_key) = match key_name.as_deref().and_then(|name| self.builder_keys.get(name)) {
+                Some((secret_key, public_key)) => (secret_key.clone(), public_key.clone()),
+                None => (self.config.secret_key.clone(), self.config.public_key.clone()),
+            };
+            match prepare_submission(payload.clone(), &secret_key, &public_key, auction, &self.context) {
+                Ok(signed_submission) => {
+                    let signed_submission = Arc::new(signed_submission);
+                    for relay_index in relay_indices {
+                        if !self.relay_health.should_dispatch(relay_index) {
+                            debug!(relay_index, slot = auction.slot, "skipping submission, circuit open");
+                            continue
                         }
-                        None => {
-                            // NOTE: this arm signals a violation of an internal invariant
-                            // Please fix if you see this error
-                            error!(relay_index, "could not dispatch to unknown relay");
+                        match self.relays.get(relay_index) {
+                            Some(relay) => {
+                                let relay = relay.clone();
+                                let signed_submission = signed_submission.clone();
+                                submissions.spawn(async move {
+                                    let start = Instant::now();
+                                    let result = timeout(
+                                        RELAY_REQUEST_TIMEOUT,
+                                        relay.submit_bid(&signed_submission),
+                                    )
+                                    .await;
+                                    (relay_index, relay, result, start.elapsed())
+                                });
+                            }
+                            None => {
+                                // NOTE: this arm signals a violation of an internal invariant
+                                // Please fix if you see this error
+                                error!(relay_index, "could not dispatch to unknown relay");
+                            }
                         }
                     }
                 }
+                Err(err) => {
+                    warn!(%err, slot = auction.slot, ?relay_indices, "could not prepare submission for builder key group")
+                }
             }
-            Err(err) => {
-                warn!(%err, slot = auction.slot, "could not prepare submission")
+        }
+
+        while let Some(result) = submissions.join_next().await {
+            match result {
+                Ok((relay_index, relay, Ok(Ok(())), latency)) => {
+                    self.relay_health.record_success(relay_index, latency);
+                    debug!(%relay, slot = auction.slot, "submitted payload");
+                }
+                Ok((relay_index, relay, Ok(Err(err)), _)) => {
+                    self.relay_health.record_failure(relay_index, err.to_string());
+                    warn!(%err, %relay, slot = auction.slot, "could not submit payload");
+                }
+                Ok((relay_index, relay, Err(_), _)) => {
+                    self.relay_health.record_failure(relay_index, "request timed out".to_string());
+                    warn!(%relay, slot = auction.slot, timeout = ?RELAY_REQUEST_TIMEOUT, "timed out submitting payload");
+                }
+                Err(err) => {
+                    error!(%err, slot = auction.slot, "submission task panicked");
+                }
             }
         }
     }
@@ -419,3 +770,99 @@ impl<
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_tracker() -> RelayHealthTracker {
+        RelayHealthTracker::new(
+            1,
+            DEFAULT_CIRCUIT_BREAKER_FAILURE_THRESHOLD,
+            Duration::from_secs(DEFAULT_CIRCUIT_BREAKER_COOLDOWN_SECS),
+        )
+    }
+
+    #[test]
+    fn circuit_trips_after_consecutive_failure_threshold() {
+        let mut tracker = test_tracker();
+        for _ in 0..DEFAULT_CIRCUIT_BREAKER_FAILURE_THRESHOLD {
+            tracker.record_failure(0, "error".to_string());
+        }
+        assert!(!tracker.should_dispatch(0));
+        assert_eq!(tracker.relays[0].state, CircuitState::Open);
+    }
+
+    #[test]
+    fn success_resets_failure_counter() {
+        let mut tracker = test_tracker();
+        for _ in 0..(DEFAULT_CIRCUIT_BREAKER_FAILURE_THRESHOLD - 1) {
+            tracker.record_failure(0, "error".to_string());
+        }
+        tracker.record_success(0, Duration::from_millis(10));
+        assert_eq!(tracker.relays[0].consecutive_failures, 0);
+
+        for _ in 0..(DEFAULT_CIRCUIT_BREAKER_FAILURE_THRESHOLD - 1) {
+            tracker.record_failure(0, "error".to_string());
+        }
+        assert!(tracker.should_dispatch(0));
+        assert_eq!(tracker.relays[0].state, CircuitState::Closed);
+    }
+
+    #[test]
+    fn single_trial_request_let_through_after_cooldown() {
+        let mut tracker = test_tracker();
+        for _ in 0..DEFAULT_CIRCUIT_BREAKER_FAILURE_THRESHOLD {
+            tracker.record_failure(0, "error".to_string());
+        }
+        assert!(!tracker.should_dispatch(0), "circuit should still be open before the cooldown elapses");
+
+        tracker.relays[0].opened_at =
+            Some(Instant::now() - tracker.cooldown - Duration::from_millis(1));
+
+        assert!(tracker.should_dispatch(0), "the trial request should be let through once cooled down");
+        assert_eq!(tracker.relays[0].state, CircuitState::HalfOpen);
+
+        assert!(!tracker.should_dispatch(0), "only a single trial request should be let through");
+        assert_eq!(tracker.relays[0].state, CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn failed_trial_reopens_circuit() {
+        let mut tracker = test_tracker();
+        for _ in 0..DEFAULT_CIRCUIT_BREAKER_FAILURE_THRESHOLD {
+            tracker.record_failure(0, "error".to_string());
+        }
+        tracker.relays[0].opened_at =
+            Some(Instant::now() - tracker.cooldown - Duration::from_millis(1));
+        assert!(tracker.should_dispatch(0));
+
+        tracker.record_failure(0, "trial failed".to_string());
+
+        assert_eq!(tracker.relays[0].state, CircuitState::Open);
+        assert!(!tracker.should_dispatch(0), "a failed trial should re-open the circuit immediately");
+    }
+
+    #[test]
+    fn first_observation_is_retained() {
+        assert!(should_retain_as_best::<&str, u64>(None, &"0xaaa", &100));
+    }
+
+    #[test]
+    fn lower_fee_payload_is_ignored() {
+        let current = Some(("0xaaa", 100u64));
+        assert!(!should_retain_as_best(current, &"0xbbb", &50));
+    }
+
+    #[test]
+    fn higher_fee_different_hash_replaces_tracked_best() {
+        let current = Some(("0xaaa", 100u64));
+        assert!(should_retain_as_best(current, &"0xbbb", &150));
+    }
+
+    #[test]
+    fn repeat_observation_of_same_hash_is_a_noop() {
+        let current = Some(("0xaaa", 100u64));
+        assert!(!should_retain_as_best(current, &"0xaaa", &100));
+    }
+}